@@ -5,7 +5,7 @@ use std::vec;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
 
-use crate::vector_store::store::VectorStore;
+use crate::vector_store::store::{Metric, VectorStore};
 wasm_bindgen_test_configure!(run_in_browser);
 
 #[wasm_bindgen]
@@ -17,7 +17,7 @@ extern "C" {
 }
 #[wasm_bindgen_test]
 async fn test_add_vectore_by_text() {
-    let mut vectore_db = VectorStore::new(None, None);
+    let mut vectore_db = VectorStore::new(None, None, None, None);
     let text = "car\nred\nbuss\nargo";
     let _ = vectore_db.add_vectore_by_text(text.to_string()).await;
 
@@ -34,7 +34,7 @@ async fn test_add_vectore_by_text() {
 
 #[wasm_bindgen_test]
 async fn test_add_vectore() {
-    let mut vectore_db = VectorStore::new(None, None);
+    let mut vectore_db = VectorStore::new(None, None, None, None);
     let text = "car";
     let _ = vectore_db.add_vectore_by_word(text.to_string()).await;
 
@@ -45,3 +45,101 @@ async fn test_add_vectore() {
 
     assert_eq!(gess.get_vector(), vec!["car".to_string()]);
 }
+
+#[wasm_bindgen_test]
+fn test_hybrid_rank_pure_keyword() {
+    let mut vectore_db = VectorStore::new(None, Some(2), None, None);
+    vectore_db
+        .add_vector_with_embedding("red car".to_string(), vec![1.0, 0.0])
+        .unwrap();
+    vectore_db
+        .add_vector_with_embedding("blue sky".to_string(), vec![0.0, 1.0])
+        .unwrap();
+    vectore_db
+        .add_vector_with_embedding("green".to_string(), vec![1.0, 1.0])
+        .unwrap();
+
+    // semantic_ratio = 0.0 reproduces pure keyword search against the query text.
+    let hits = vectore_db.hybrid_rank("green", &vec![1.0, 1.0], 1, 0.0);
+    assert_eq!(hits, vec!["green".to_string()]);
+}
+
+#[wasm_bindgen_test]
+fn test_hybrid_rank_empty_store() {
+    let vectore_db = VectorStore::new(None, Some(2), None, None);
+    assert!(vectore_db
+        .hybrid_rank("anything", &vec![0.0, 0.0], 5, 0.5)
+        .is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_similar_scored_range() {
+    let mut vectore_db = VectorStore::new(None, Some(2), None, None);
+    vectore_db
+        .add_vector_with_embedding("a".to_string(), vec![1.0, 0.0])
+        .unwrap();
+    vectore_db
+        .add_vector_with_embedding("b".to_string(), vec![0.0, 1.0])
+        .unwrap();
+
+    let scored = vectore_db.find_similers_scored(&vec![1.0, 0.0], 2);
+    assert_eq!(scored.len(), 2);
+    // Nearest neighbor first, and an exact match maps to a similarity of 1.0.
+    assert_eq!(scored[0].0, "a".to_string());
+    assert!((scored[0].1 - 1.0).abs() < 1e-9);
+    for (_, score) in &scored {
+        assert!(*score >= 0.0 && *score <= 1.0);
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_cosine_ranks_by_angle() {
+    let mut vectore_db = VectorStore::new(None, Some(2), None, Some(Metric::Cosine));
+    // Same direction as the query but large magnitude: closest by angle, farthest by raw distance.
+    vectore_db
+        .add_vector_with_embedding("aligned".to_string(), vec![10.0, 0.0])
+        .unwrap();
+    // Nearer in raw euclidean terms, but off-angle from the query.
+    vectore_db
+        .add_vector_with_embedding("close".to_string(), vec![0.9, 0.5])
+        .unwrap();
+
+    // Under cosine the aligned vector wins despite being far in raw euclidean distance.
+    let hits = vectore_db.similar_to_embedding(vec![1.0, 0.0], 2).unwrap();
+    assert_eq!(hits.get_vector()[0], "aligned".to_string());
+}
+
+#[wasm_bindgen_test]
+fn test_export_import_round_trip() {
+    let mut vectore_db = VectorStore::new(None, Some(2), None, None);
+    vectore_db
+        .add_vector_with_embedding("a".to_string(), vec![1.0, 0.0])
+        .unwrap();
+    vectore_db
+        .add_vector_with_embedding("b".to_string(), vec![0.0, 1.0])
+        .unwrap();
+
+    let json = vectore_db.export_json().unwrap();
+    let restored = VectorStore::from_json(json).unwrap();
+    assert_eq!(restored.get_words(), vec!["a".to_string(), "b".to_string()]);
+    // The rebuilt k-d tree answers queries identically to the original store.
+    let hits = restored.similar_to_embedding(vec![1.0, 0.0], 1).unwrap();
+    assert_eq!(hits.get_vector(), vec!["a".to_string()]);
+}
+
+#[wasm_bindgen_test]
+fn test_from_json_rejects_corrupt_snapshot() {
+    let bad = "{\"words\":[\"a\",\"b\"],\"vectors\":[[1.0,0.0]],\"vectore_dimension\":2,\"metric\":\"Euclidean\"}";
+    assert!(VectorStore::from_json(bad.to_string()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_add_rejects_wrong_dimension_and_non_finite() {
+    let mut vectore_db = VectorStore::new(None, Some(2), None, None);
+    assert!(vectore_db
+        .add_vector_with_embedding("short".to_string(), vec![1.0])
+        .is_err());
+    assert!(vectore_db
+        .add_vector_with_embedding("nan".to_string(), vec![1.0, f64::NAN])
+        .is_err());
+}
@@ -6,6 +6,13 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{Request, RequestInit, RequestMode, Response};
 
+/// Initial backoff delay in milliseconds used when retrying a throttled batch.
+const BASE_DELAY_MS: f64 = 200.0;
+/// Upper bound in milliseconds on a single backoff delay.
+const MAX_DELAY_MS: f64 = 3000.0;
+/// Maximum number of attempts for a single batch before giving up.
+const MAX_ATTEMPTS: u32 = 5;
+
 #[derive(Deserialize, Debug)]
 pub struct MessageDeserializer {
     pub data: Vec<f64>,
@@ -60,22 +67,71 @@ pub async fn get_embbide_request_array(
     url: &str,
 ) -> Result<F64ArrayCollection, JsValue> {
     log(&url);
-    let mut opts = RequestInit::new();
-    opts.method("POST");
-    opts.mode(RequestMode::Cors);
     let json_data = serde_json::to_string(word_array).unwrap();
-    let body = JsValue::from_str(&json_data);
-    opts.body(Some(&body));
     let url = format!("{}list", url);
-    let request = Request::new_with_str_and_init(&url, &opts)?;
-    request.headers().set("Accept", "application/json")?;
-    request.headers().set("Content-Type", "application/json")?;
     let window = web_sys::window().unwrap_throw();
-    let resp_value = JsFuture::from(window.fetch_with_request(&request)).await?;
-    assert!(resp_value.is_instance_of::<Response>());
-    let resp: Response = resp_value.dyn_into().unwrap();
-    let json = JsFuture::from(resp.json()?).await?;
-    let result: ArrayMessageDeserializer = serde_wasm_bindgen::from_value(json)?;
-    let vectore = F64ArrayCollection::new(result.data);
-    Ok(vectore)
+
+    let mut attempt = 0u32;
+    loop {
+        let mut opts = RequestInit::new();
+        opts.method("POST");
+        opts.mode(RequestMode::Cors);
+        let body = JsValue::from_str(&json_data);
+        opts.body(Some(&body));
+        let request = Request::new_with_str_and_init(&url, &opts)?;
+        request.headers().set("Accept", "application/json")?;
+        request.headers().set("Content-Type", "application/json")?;
+
+        let resp_value = JsFuture::from(window.fetch_with_request(&request)).await?;
+        assert!(resp_value.is_instance_of::<Response>());
+        let resp: Response = resp_value.dyn_into().unwrap();
+
+        if resp.ok() {
+            let json = JsFuture::from(resp.json()?).await?;
+            let result: ArrayMessageDeserializer = serde_wasm_bindgen::from_value(json)?;
+            return Ok(F64ArrayCollection::new(result.data));
+        }
+
+        attempt += 1;
+        if attempt >= MAX_ATTEMPTS {
+            return Err(JsValue::from_str(&format!(
+                "embedding request failed with status {} after {} attempts",
+                resp.status(),
+                attempt
+            )));
+        }
+
+        // A 429 may advertise an explicit `Retry-After`; otherwise back off exponentially.
+        let delay = match retry_after_ms(&resp) {
+            Some(ms) => ms,
+            None => {
+                let backoff = BASE_DELAY_MS * 2f64.powi(attempt as i32 - 1);
+                let jitter = js_sys::Math::random() * BASE_DELAY_MS;
+                (backoff + jitter).min(MAX_DELAY_MS)
+            }
+        };
+        sleep(delay).await?;
+    }
+}
+
+/// Reads a `Retry-After` header (in seconds) from a response and converts it to milliseconds.
+fn retry_after_ms(resp: &Response) -> Option<f64> {
+    resp.headers()
+        .get("Retry-After")
+        .ok()
+        .flatten()
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .map(|secs| secs * 1000.0)
+}
+
+/// Resolves after the given number of milliseconds, backing `setTimeout` with a promise.
+async fn sleep(ms: f64) -> Result<(), JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        web_sys::window()
+            .unwrap_throw()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms as i32)
+            .unwrap();
+    });
+    JsFuture::from(promise).await?;
+    Ok(())
 }
@@ -1,8 +1,21 @@
 use super::request_util;
 use kdtree::distance::squared_euclidean;
 use kdtree::KdTree;
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
+/// The distance metric a `VectorStore` uses to rank nearest neighbors.
+///
+/// * `Euclidean`: raw squared-euclidean distance over the stored vectors.
+/// * `Cosine`: cosine similarity, realized by L2-normalizing vectors so that squared-euclidean
+///             nearest-neighbor becomes rank-equivalent to cosine similarity.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Metric {
+    Euclidean,
+    Cosine,
+}
+
 /// `VectorStore` is a data structure that allows storing and querying
 /// high-dimensional vectors (embeddings) efficiently using a k-d tree.
 ///
@@ -11,12 +24,30 @@ use wasm_bindgen::prelude::*;
 /// * `db`: A `KdTree` data structure for indexing the high-dimensional vectors.
 /// * `url`: An optional field to store a URL related to the `VectorStore`.
 /// * `words`: A list of words, where each word is associated with a vector in the `db`.
+/// * `cache`: A map from word to its embedding, consulted before issuing a remote fetch.
+/// * `caching_enabled`: Whether embedding responses are stored in and served from `cache`.
 #[wasm_bindgen]
 #[derive(Clone, Debug)]
 pub struct VectorStore {
     db: KdTree<f64, i32, Vec<f64>>,
     url: String,
     words: Vec<String>,
+    cache: HashMap<String, Vec<f64>>,
+    caching_enabled: bool,
+    batch_size: usize,
+    vectore_dimension: usize,
+    metric: Metric,
+    vectors: Vec<Vec<f64>>,
+}
+
+/// The serialized form of a `VectorStore`, holding every word alongside its stored embedding so
+/// the whole index can be persisted and rebuilt without re-fetching from the embedding server.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct VectorStoreSnapshot {
+    words: Vec<String>,
+    vectors: Vec<Vec<f64>>,
+    vectore_dimension: usize,
+    metric: Metric,
 }
 
 #[wasm_bindgen]
@@ -28,17 +59,83 @@ impl VectorStore {
     ///
     /// * `url` - An optional String representing the URL.
     /// * `vectore_dimension` - An optional usize representing the dimension of vectors. If not provided, defaults to 384.
+    /// * `enable_cache` - An optional bool toggling the local embedding cache. If not provided, defaults to true.
+    /// * `metric` - An optional `Metric` selecting the distance metric. If not provided, defaults to `Euclidean`.
     ///
     /// # Returns
     ///
     /// A new instance of the struct with the specified URL, vector dimension, and an empty KdTree.
-    pub fn new(url: Option<String>, vectore_dimension: Option<usize>) -> Self {
+    pub fn new(
+        url: Option<String>,
+        vectore_dimension: Option<usize>,
+        enable_cache: Option<bool>,
+        metric: Option<Metric>,
+    ) -> Self {
         let vectore_dimension = vectore_dimension.unwrap_or(384);
         let url = url.unwrap_or("https://embidded-serever.onrender.com/".to_string());
         Self {
             url,
             db: KdTree::new(vectore_dimension),
             words: Vec::new(),
+            cache: HashMap::new(),
+            caching_enabled: enable_cache.unwrap_or(true),
+            batch_size: 20,
+            vectore_dimension,
+            metric: metric.unwrap_or(Metric::Euclidean),
+            vectors: Vec::new(),
+        }
+    }
+    /// Serializes the whole store — every word and its stored embedding — into a JSON string that
+    /// a browser app can cache (e.g. in IndexedDB/localStorage) and later restore with `from_json`,
+    /// avoiding a network-bound re-ingestion on cold start.
+    pub fn export_json(&self) -> Result<String, JsValue> {
+        let snapshot = VectorStoreSnapshot {
+            words: self.words.clone(),
+            vectors: self.vectors.clone(),
+            vectore_dimension: self.vectore_dimension,
+            metric: self.metric,
+        };
+        serde_json::to_string(&snapshot).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+    /// Rebuilds a store from a JSON string produced by `export_json`, replaying `add` for each
+    /// `(word, vector)` pair so the `KdTree` is reconstructed. The remote `url` and caching default
+    /// to the values a fresh `VectorStore::new` would use.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<VectorStore, JsValue>` - Ok(VectorStore) on success, or Err(JsValue) if the JSON
+    ///   is malformed, the word and vector counts disagree, or any vector's dimension mismatches.
+    pub fn from_json(data: String) -> Result<VectorStore, JsValue> {
+        let snapshot: VectorStoreSnapshot =
+            serde_json::from_str(&data).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if snapshot.words.len() != snapshot.vectors.len() {
+            return Err(JsValue::from_str(&format!(
+                "corrupt snapshot: {} words but {} vectors",
+                snapshot.words.len(),
+                snapshot.vectors.len()
+            )));
+        }
+        let mut store = VectorStore::new(
+            None,
+            Some(snapshot.vectore_dimension),
+            None,
+            Some(snapshot.metric),
+        );
+        for (word, vector) in snapshot.words.into_iter().zip(snapshot.vectors) {
+            store.check_dimension(vector.len())?;
+            store.add(word, vector)?;
+        }
+        Ok(store)
+    }
+    /// Clears the local embedding cache, forcing subsequent lookups to re-fetch from the server.
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+    /// Sets the number of words sent per batch when ingesting text with `add_vectore_by_text`.
+    /// A zero value is ignored so ingestion always makes progress.
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        if batch_size > 0 {
+            self.batch_size = batch_size;
         }
     }
     /// Asynchronously adds a vector by word to the KdTree.
@@ -59,9 +156,8 @@ impl VectorStore {
     /// If successful, it adds the word and its vector to the KdTree.
 
     pub async fn add_vectore_by_word(&mut self, name: String) -> Result<(), JsValue> {
-        let vector = request_util::get_embbide_request(&name, &self.url).await?;
-        self.add(name, vector.0);
-        Ok(())
+        let vector = self.get_embedding(&name).await?;
+        self.add(name, vector)
     }
     /// Asynchronously adds vectors by text to the KdTree.
     ///
@@ -78,15 +174,17 @@ impl VectorStore {
     /// # Remarks
     ///
     /// This function splits the input text into an array of words using newline ('\n') as the delimiter.
-    /// It then iterates over chunks of 50 words at a time, retrieves their embedding vectors asynchronously
-    /// using `request_util::get_embbide_request_array`, and adds each word and its corresponding vector to the KdTree.
+    /// It then iterates over chunks of `batch_size` words at a time (configurable via `set_batch_size`),
+    /// retrieves their embedding vectors asynchronously using `request_util::get_embbide_request_array`,
+    /// and adds each word and its corresponding vector to the KdTree before fetching the next batch, so a
+    /// later failure does not discard already-embedded words.
 
     pub async fn add_vectore_by_text(&mut self, text: String) -> Result<(), JsValue> {
         let word_array = text.split('\n').collect::<Vec<&str>>();
-        for i in word_array.chunks(20) {
-            let vector = request_util::get_embbide_request_array(i, &self.url).await?;
-            for j in 0..vector.0.len() {
-                self.add(i[j].to_string(), vector.0[j].clone());
+        for i in word_array.chunks(self.batch_size) {
+            let vectors = self.get_embedding_array(i).await?;
+            for j in 0..vectors.len() {
+                self.add(i[j].to_string(), vectors[j].clone())?;
             }
         }
 
@@ -120,29 +218,339 @@ impl VectorStore {
     /// It then finds the top `top_k` similar words based on the cosine similarity of their vectors in the KdTree.
     /// The result is wrapped in a StringCollection.
     pub async fn similar_words(
-        &self,
+        &mut self,
         word: String,
         top_k: usize,
     ) -> Result<StringCollection, JsValue> {
-        let vector = request_util::get_embbide_request(&word, &self.url).await?;
-        let top_k_vec = self.find_similers(&vector.0, top_k);
+        let vector = self.get_embedding(&word).await?;
+        let top_k_vec = self.find_similers(&vector, top_k);
         Ok(StringCollection(top_k_vec))
     }
+    /// Asynchronously retrieves similar words by fusing a semantic (embedding) ranking
+    /// with a lexical (keyword) ranking over the stored `words`.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - A String representing the query to search for.
+    /// * `top_k` - A usize specifying the number of results to retrieve.
+    /// * `semantic_ratio` - A f64 in `[0, 1]` weighting the semantic channel against the
+    ///                      keyword channel. `1.0` reproduces pure vector search and `0.0`
+    ///                      reproduces pure keyword search.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<StringCollection, JsValue>` - A Result indicating success or an error wrapped in JsValue.
+    ///                                        - Ok(StringCollection) containing the fused top `top_k` words.
+    ///                                        - Err(JsValue) if there is an error during the asynchronous operation.
+    ///
+    /// # Remarks
+    ///
+    /// The embedding vector for `query` is fetched asynchronously using
+    /// `request_util::get_embbide_request` to drive the semantic ranking, while the keyword
+    /// ranking scores each stored word by normalized token overlap against `query`. The two
+    /// rankings are combined with Reciprocal Rank Fusion using a constant of `k0 = 60`.
+    pub async fn hybrid_search(
+        &mut self,
+        query: String,
+        top_k: usize,
+        semantic_ratio: f64,
+    ) -> Result<StringCollection, JsValue> {
+        let vector = self.get_embedding(&query).await?;
+        let top_k_vec = self.hybrid_rank(&query, &vector, top_k, semantic_ratio);
+        Ok(StringCollection(top_k_vec))
+    }
+    /// Adds a word together with a caller-supplied embedding, bypassing the embedding server.
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - A String representing the word.
+    /// * `embedding` - A Vec<f64> holding the embedding vector for the word.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), JsValue>` - Ok(()) if the embedding matches the store's dimension and holds
+    ///                           only finite values, or Err(JsValue) describing the problem otherwise.
+    pub fn add_vector_with_embedding(
+        &mut self,
+        word: String,
+        embedding: Vec<f64>,
+    ) -> Result<(), JsValue> {
+        self.check_dimension(embedding.len())?;
+        self.add(word, embedding)
+    }
+    /// Adds several words together with caller-supplied embeddings, bypassing the embedding server.
+    ///
+    /// # Arguments
+    ///
+    /// * `words` - A Vec<String> of words to add.
+    /// * `embeddings` - A flat Vec<f64> holding the words' embeddings concatenated in order, so its
+    ///                  length must be `words.len() * vectore_dimension`. A flat buffer is used so
+    ///                  JS callers can pass a plain `Float64Array` across the wasm boundary.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), JsValue>` - Ok(()) if the length is an exact multiple matching the word count
+    ///                           and dimension and every value is finite, or Err(JsValue)
+    ///                           describing the problem otherwise. No word is added on error.
+    pub fn add_vectors_with_embeddings(
+        &mut self,
+        words: Vec<String>,
+        embeddings: Vec<f64>,
+    ) -> Result<(), JsValue> {
+        if embeddings.len() != words.len() * self.vectore_dimension {
+            return Err(JsValue::from_str(&format!(
+                "expected {} words * {} dims = {} values, got {}",
+                words.len(),
+                self.vectore_dimension,
+                words.len() * self.vectore_dimension,
+                embeddings.len()
+            )));
+        }
+        if let Some(pos) = embeddings.iter().position(|x| !x.is_finite()) {
+            return Err(JsValue::from_str(&format!(
+                "embedding contains a non-finite value at index {}",
+                pos
+            )));
+        }
+        for (word, embedding) in words
+            .into_iter()
+            .zip(embeddings.chunks(self.vectore_dimension))
+        {
+            self.add(word, embedding.to_vec())?;
+        }
+        Ok(())
+    }
+    /// Finds the `top_k` words nearest to a caller-supplied query embedding, bypassing the
+    /// embedding server.
+    ///
+    /// # Arguments
+    ///
+    /// * `embedding` - A Vec<f64> holding the raw query vector.
+    /// * `top_k` - A usize specifying the number of similar words to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<StringCollection, JsValue>` - Ok(StringCollection) with the nearest words, or
+    ///                                         Err(JsValue) if the embedding dimension mismatches.
+    pub fn similar_to_embedding(
+        &self,
+        embedding: Vec<f64>,
+        top_k: usize,
+    ) -> Result<StringCollection, JsValue> {
+        self.check_dimension(embedding.len())?;
+        Ok(StringCollection(self.find_similers(&embedding, top_k)))
+    }
+    /// Asynchronously retrieves similar words to the given word together with a similarity score.
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - A String representing the word to find similar words for.
+    /// * `top_k` - A usize specifying the number of similar words to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ScoredCollection, JsValue>` - Ok(ScoredCollection) pairing each matched word with
+    ///                                         a derived similarity in `[0, 1]`, or Err(JsValue) on
+    ///                                         a failed embedding fetch.
+    ///
+    /// # Remarks
+    ///
+    /// Unlike `similar_words`, this keeps the distances that `KdTree::nearest` computes and maps
+    /// each to a similarity in `[0, 1]`, letting consumers rank results and apply their own
+    /// confidence thresholds.
+    pub async fn similar_words_scored(
+        &mut self,
+        word: String,
+        top_k: usize,
+    ) -> Result<ScoredCollection, JsValue> {
+        let vector = self.get_embedding(&word).await?;
+        Ok(ScoredCollection(self.find_similers_scored(&vector, top_k)))
+    }
 }
 
 impl VectorStore {
-    pub fn add(&mut self, word: String, vector: Vec<f64>) {
+    /// Fetches the embedding for a single word, serving it from the local cache when caching is
+    /// enabled and the word is present, and populating the cache on every successful fetch.
+    async fn get_embedding(&mut self, word: &str) -> Result<Vec<f64>, JsValue> {
+        if self.caching_enabled {
+            if let Some(vector) = self.cache.get(word) {
+                return Ok(vector.clone());
+            }
+        }
+        let vector = request_util::get_embbide_request(word, &self.url).await?.0;
+        if self.caching_enabled {
+            self.cache.insert(word.to_string(), vector.clone());
+        }
+        Ok(vector)
+    }
+
+    /// Fetches embeddings for an array of words, serving already-cached words without a request and
+    /// fetching only the misses in a single batch. When caching is enabled, all responses are
+    /// written back into the cache; when it is disabled, the whole array is fetched and nothing is cached.
+    async fn get_embedding_array(&mut self, word_array: &[&str]) -> Result<Vec<Vec<f64>>, JsValue> {
+        if !self.caching_enabled {
+            return Ok(request_util::get_embbide_request_array(word_array, &self.url)
+                .await?
+                .into_inner());
+        }
+
+        let missing = word_array
+            .iter()
+            .filter(|w| !self.cache.contains_key(**w))
+            .copied()
+            .collect::<Vec<&str>>();
+        if !missing.is_empty() {
+            let fetched = request_util::get_embbide_request_array(&missing, &self.url)
+                .await?
+                .into_inner();
+            if fetched.len() != missing.len() {
+                return Err(JsValue::from_str(&format!(
+                    "embedding server returned {} vectors for {} words",
+                    fetched.len(),
+                    missing.len()
+                )));
+            }
+            for (word, vector) in missing.iter().zip(fetched) {
+                self.cache.insert(word.to_string(), vector);
+            }
+        }
+        Ok(word_array
+            .iter()
+            .map(|w| self.cache[*w].clone())
+            .collect::<Vec<Vec<f64>>>())
+    }
+
+    /// Validates that a supplied embedding length matches the store's configured dimension,
+    /// returning a descriptive `Err(JsValue)` instead of letting `KdTree::add` surface a
+    /// cryptic error downstream.
+    fn check_dimension(&self, len: usize) -> Result<(), JsValue> {
+        if len != self.vectore_dimension {
+            return Err(JsValue::from_str(&format!(
+                "embedding dimension mismatch: expected {}, got {}",
+                self.vectore_dimension, len
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn add(&mut self, word: String, vector: Vec<f64>) -> Result<(), JsValue> {
+        if let Some(pos) = vector.iter().position(|x| !x.is_finite()) {
+            return Err(JsValue::from_str(&format!(
+                "embedding contains a non-finite value at index {}",
+                pos
+            )));
+        }
+        let prepared = self.prepare(vector.clone());
+        let index = self.words.len() as i32;
+        self.db
+            .add(prepared, index)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
         self.words.push(word);
-        self.db.add(vector, self.words.len() as i32 - 1).unwrap();
+        self.vectors.push(vector);
+        Ok(())
+    }
+
+    /// Prepares a vector for insertion or querying according to the configured metric:
+    /// under `Cosine` the vector is L2-normalized so that squared-euclidean nearest-neighbor is
+    /// rank-equivalent to cosine similarity; under `Euclidean` it is returned unchanged.
+    fn prepare(&self, vector: Vec<f64>) -> Vec<f64> {
+        match self.metric {
+            Metric::Euclidean => vector,
+            Metric::Cosine => {
+                let norm = vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+                if norm == 0.0 {
+                    vector
+                } else {
+                    vector.iter().map(|x| x / norm).collect()
+                }
+            }
+        }
     }
 
     pub fn find_similers(&self, vector: &Vec<f64>, top_k: usize) -> Vec<String> {
-        let result = self.db.nearest(vector, top_k, &squared_euclidean).unwrap();
+        let vector = self.prepare(vector.clone());
+        let result = self.db.nearest(&vector, top_k, &squared_euclidean).unwrap();
         result
             .iter()
             .map(|&(_, i)| self.words[*i as usize].clone())
             .collect::<Vec<String>>()
     }
+
+    pub fn find_similers_scored(&self, vector: &Vec<f64>, top_k: usize) -> Vec<(String, f64)> {
+        let vector = self.prepare(vector.clone());
+        let result = self.db.nearest(&vector, top_k, &squared_euclidean).unwrap();
+        result
+            .iter()
+            .map(|&(distance, i)| (self.words[*i as usize].clone(), 1.0 / (1.0 + distance)))
+            .collect::<Vec<(String, f64)>>()
+    }
+
+    /// Fuses a semantic ranking (embedding nearest-neighbor over `vector`) with a keyword
+    /// ranking (token overlap of each stored word against `query`) using Reciprocal Rank
+    /// Fusion, and returns the fused top `top_k` words.
+    pub fn hybrid_rank(
+        &self,
+        query: &str,
+        vector: &Vec<f64>,
+        top_k: usize,
+        semantic_ratio: f64,
+    ) -> Vec<String> {
+        const K0: f64 = 60.0;
+
+        // An empty store has nothing to rank; avoid unwrapping a `nearest` error on valid input.
+        if self.words.is_empty() {
+            return Vec::new();
+        }
+
+        // Semantic channel: rank every indexed word by embedding nearest-neighbor.
+        let prepared = self.prepare(vector.clone());
+        let semantic = self
+            .db
+            .nearest(&prepared, self.words.len(), &squared_euclidean)
+            .unwrap();
+        let mut scores = vec![0.0f64; self.words.len()];
+        for (rank, &(_, i)) in semantic.iter().enumerate() {
+            scores[*i as usize] += semantic_ratio / (K0 + rank as f64);
+        }
+
+        // Keyword channel: rank every word by descending lexical score against the query.
+        let mut keyword = (0..self.words.len())
+            .map(|i| (i, keyword_score(query, &self.words[i])))
+            .collect::<Vec<(usize, f64)>>();
+        keyword.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        for (rank, &(i, _)) in keyword.iter().enumerate() {
+            scores[i] += (1.0 - semantic_ratio) / (K0 + rank as f64);
+        }
+
+        let mut fused = (0..self.words.len()).collect::<Vec<usize>>();
+        fused.sort_by(|&a, &b| scores[b].partial_cmp(&scores[a]).unwrap());
+        fused
+            .into_iter()
+            .take(top_k)
+            .map(|i| self.words[i].clone())
+            .collect::<Vec<String>>()
+    }
+}
+
+/// Computes a normalized token-overlap score in `[0, 1]` between a query and a candidate word.
+/// The score is the size of the intersection of their whitespace-separated tokens over the size
+/// of their union (Jaccard similarity), so an exact match scores `1.0` and disjoint words `0.0`.
+fn keyword_score(query: &str, word: &str) -> f64 {
+    let mut query_tokens = query.split_whitespace().collect::<Vec<&str>>();
+    let mut word_tokens = word.split_whitespace().collect::<Vec<&str>>();
+    query_tokens.sort_unstable();
+    query_tokens.dedup();
+    word_tokens.sort_unstable();
+    word_tokens.dedup();
+    if query_tokens.is_empty() || word_tokens.is_empty() {
+        return 0.0;
+    }
+    let intersection = query_tokens
+        .iter()
+        .filter(|t| word_tokens.contains(t))
+        .count();
+    let union = query_tokens.len() + word_tokens.len() - intersection;
+    intersection as f64 / union as f64
 }
 
 #[wasm_bindgen]
@@ -217,6 +625,15 @@ impl F64ArrayCollection {
     pub fn get_vector_by_index(&self, index: usize) -> Vec<f64> {
         self.0.get(index).unwrap().clone()
     }
+
+    /// Consumes the collection and returns the underlying vectors of f64 values.
+    ///
+    /// # Returns
+    ///
+    /// The owned vector of vectors of f64 values backing the collection.
+    pub fn into_inner(self) -> Vec<Vec<f64>> {
+        self.0
+    }
 }
 
 /// A collection of strings.
@@ -261,3 +678,54 @@ impl StringCollection {
         self.0.clone()
     }
 }
+
+/// A collection of words paired with a similarity score in `[0, 1]`.
+/// Use `get_words` to read the ranked words and `get_scores` to read their matching similarities;
+/// the two vectors are aligned by index.
+#[wasm_bindgen]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ScoredCollection(Vec<(String, f64)>);
+
+#[wasm_bindgen]
+impl ScoredCollection {
+    /// Constructs a new ScoredCollection from aligned vectors of words and scores.
+    ///
+    /// # Arguments
+    ///
+    /// * `words` - A vector containing the words.
+    /// * `scores` - A vector containing the matching similarities, aligned by index.
+    ///
+    /// # Returns
+    ///
+    /// A new ScoredCollection instance pairing each word with its score.
+    pub fn new(words: Vec<String>, scores: Vec<f64>) -> Self {
+        Self(words.into_iter().zip(scores).collect())
+    }
+
+    /// Returns the number of entries in the collection.
+    ///
+    /// # Returns
+    ///
+    /// The number of (word, score) entries in the collection.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Retrieves the matched words in ranked order.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the words, aligned by index with `get_scores`.
+    pub fn get_words(&self) -> Vec<String> {
+        self.0.iter().map(|(word, _)| word.clone()).collect()
+    }
+
+    /// Retrieves the similarity scores in ranked order.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the similarities in `[0, 1]`, aligned by index with `get_words`.
+    pub fn get_scores(&self) -> Vec<f64> {
+        self.0.iter().map(|(_, score)| *score).collect()
+    }
+}